@@ -118,16 +118,68 @@ pub struct PublicKeyCredentialRequestOptions {
     pub user_verification: Option<String>,
 }
 
+#[napi(object)]
+pub struct AuthenticatorAttestationResponse {
+    pub client_data_json: Buffer,
+    pub attestation_object: Buffer,
+    pub transports: Vec<String>,
+}
+
+#[napi(object)]
+pub struct AuthenticatorAssertionResponse {
+    pub client_data_json: Buffer,
+    pub authenticator_data: Buffer,
+    pub signature: Buffer,
+    #[napi(ts_type = "Buffer | undefined")]
+    pub user_handle: Option<Buffer>,
+}
+
+#[napi(object)]
+pub struct AuthenticationExtensionsPRFValuesOutput {
+    pub first: Buffer,
+    #[napi(ts_type = "Buffer | undefined")]
+    pub second: Option<Buffer>,
+}
+
+#[napi(object)]
+pub struct AuthenticationExtensionsPRFOutputs {
+    #[napi(ts_type = "boolean | undefined")]
+    pub enabled: Option<bool>,
+    #[napi(ts_type = "AuthenticationExtensionsPRFValuesOutput | undefined")]
+    pub results: Option<AuthenticationExtensionsPRFValuesOutput>,
+}
+
+#[napi(object)]
+pub struct AuthenticationExtensionsCredentialPropertiesOutput {
+    #[napi(ts_type = "boolean | undefined")]
+    pub rk: Option<bool>,
+}
+
+#[napi(object)]
+#[derive(Default)]
+pub struct AuthenticationExtensionsClientOutputs {
+    #[napi(ts_type = "string | undefined")]
+    pub appid: Option<String>,
+    #[napi(ts_type = "boolean | undefined")]
+    pub appid_exclude: Option<bool>,
+    #[napi(ts_type = "AuthenticationExtensionsCredentialPropertiesOutput | undefined")]
+    pub cred_props: Option<AuthenticationExtensionsCredentialPropertiesOutput>,
+    #[napi(ts_type = "AuthenticationExtensionsPRFOutputs | undefined")]
+    pub prf: Option<AuthenticationExtensionsPRFOutputs>,
+}
+
 #[napi(object)]
 pub struct PublicKeyCredential {
     pub id: String,
     pub raw_id: Buffer,
-    // Simplified response - can be extended based on needs
-    pub response: Buffer,
+    #[napi(ts_type = "AuthenticatorAttestationResponse | AuthenticatorAssertionResponse")]
+    pub response: Either<AuthenticatorAttestationResponse, AuthenticatorAssertionResponse>,
     #[napi(ts_type = "string | undefined")]
     pub authenticator_attachment: Option<String>,
     #[napi(js_name = "type")]
     pub type_: String,
+    /// Mirrors `PublicKeyCredential.getClientExtensionResults()` from the WebAuthn spec.
+    pub client_extension_results: AuthenticationExtensionsClientOutputs,
 }
 
 /// Create a new WebAuthn credential
@@ -145,4 +197,73 @@ pub async fn get(options: PublicKeyCredentialRequestOptions) -> Result<PublicKey
 #[napi]
 pub async fn is_supported() -> Result<bool> {
     platform::is_supported().await
+}
+
+/// Route all subsequent `create`/`get` calls to the in‑process software
+/// authenticator instead of the OS backend. Registered virtual authenticators
+/// still take precedence over it. Intended for CI and headless testing;
+/// requires the crate's `software-authenticator` feature.
+#[napi]
+pub fn use_software_authenticator() {
+    platform::use_software_authenticator();
+}
+
+// -------------------------------------------------------------------------------------------------
+//  Virtual authenticator management (WebDriver-style), for scripted automated testing.
+// -------------------------------------------------------------------------------------------------
+
+#[napi(object)]
+pub struct VirtualAuthenticatorOptions {
+    #[napi(ts_type = "\"ctap2\" | \"u2f\"")]
+    pub protocol: String,
+    #[napi(ts_type = "\"ble\" | \"internal\" | \"nfc\" | \"usb\"")]
+    pub transport: String,
+    #[napi(ts_type = "boolean | undefined")]
+    pub has_resident_key: Option<bool>,
+    #[napi(ts_type = "boolean | undefined")]
+    pub has_user_verification: Option<bool>,
+    #[napi(ts_type = "boolean | undefined")]
+    pub is_user_consenting: Option<bool>,
+}
+
+#[napi(object)]
+pub struct VirtualAuthenticatorCredential {
+    pub credential_id: Buffer,
+    pub rp_id: String,
+    /// PKCS#8 DER-encoded P-256 private key.
+    pub private_key: Buffer,
+    pub sign_count: u32,
+    #[napi(ts_type = "Buffer | undefined")]
+    pub user_handle: Option<Buffer>,
+}
+
+/// Register a new virtual authenticator. While at least one is registered,
+/// `create`/`get` are serviced by the virtual authenticators instead of the
+/// OS backend, so test harnesses can script success/failure (`isUserConsenting`)
+/// and UV state (`hasUserVerification`, `setUserVerified`) deterministically.
+/// `hasResidentKey` and `protocol` are stored but do not change how
+/// `create`/`get` behave.
+#[napi]
+pub fn add_virtual_authenticator(options: VirtualAuthenticatorOptions) -> Result<String> {
+    platform::virtual_authenticator::add(options)
+}
+
+#[napi]
+pub fn remove_virtual_authenticator(authenticator_id: String) -> Result<()> {
+    platform::virtual_authenticator::remove(&authenticator_id)
+}
+
+#[napi]
+pub fn add_credential(authenticator_id: String, credential: VirtualAuthenticatorCredential) -> Result<()> {
+    platform::virtual_authenticator::add_credential(&authenticator_id, credential)
+}
+
+#[napi]
+pub fn get_credentials(authenticator_id: String) -> Result<Vec<VirtualAuthenticatorCredential>> {
+    platform::virtual_authenticator::get_credentials(&authenticator_id)
+}
+
+#[napi]
+pub fn set_user_verified(authenticator_id: String, verified: bool) -> Result<()> {
+    platform::virtual_authenticator::set_user_verified(&authenticator_id, verified)
 }
\ No newline at end of file
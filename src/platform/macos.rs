@@ -1,27 +1,38 @@
 //! macOS WebAuthn / Passkey glue using Apple AuthenticationServices (Ventura+)
 //! ---------------------------------------------------------------------------
-//! This file provides the synchronous `create_credential_impl` and
-//! `get_credential_impl` helpers used by the napi‐rs layer.  Internally we
-//! exercise the Objective‑C `ASAuthorizationPlatformPublicKeyCredentialProvider`
-//! API when it is available; outside unit tests we still fall back to a fully
-//! in‑process mock so that the Rust crate can build and unit‑test on CI without
-//! real macOS UI interaction.  Swap the mock for a real async delegate when
-//! ready.
+//! This file provides the `async` `create_credential_impl` and
+//! `get_credential_impl` helpers used by the napi‑rs layer. Registration and
+//! assertion requests are handed to a real `ASAuthorizationController`: we
+//! declare an Objective‑C delegate at runtime that implements
+//! `authorizationController:didCompleteWithAuthorization:` and
+//! `authorizationController:didCompleteWithError:`, present it on the main
+//! thread via a presentation‑context provider, and forward the delegate
+//! callback through a oneshot channel so the `async` napi function resolves
+//! exactly when macOS hands back (or rejects) the authorization.
 
 #![cfg(target_os = "macos")]
 #![allow(non_snake_case, clippy::needless_return)]
 
-use objc::rc::{autoreleasepool};
-use objc::{msg_send, sel, sel_impl};
-use objc::runtime::{Class, Object};
+use std::os::raw::c_void;
+use std::sync::Once;
+
+use objc::declare::ClassDecl;
+use objc::rc::{autoreleasepool, StrongPtr};
+use objc::runtime::{Class, Object, Sel};
+use objc::{class, msg_send, sel, sel_impl};
 
 use objc_foundation::{INSData, INSString, NSData, NSString};
 
 use napi::{bindgen_prelude::*, Error, Result, Status};
+use tokio::sync::oneshot;
 
 use crate::{
-    AuthenticatorAssertionResponse, AuthenticatorAttestationResponse, PublicKeyCredential,
-    PublicKeyCredentialCreationOptions, PublicKeyCredentialRequestOptions,
+    AuthenticationExtensionsClientInputs, AuthenticationExtensionsClientOutputs,
+    AuthenticationExtensionsCredentialPropertiesOutput, AuthenticationExtensionsPRFInputs,
+    AuthenticationExtensionsPRFOutputs, AuthenticationExtensionsPRFValues, AuthenticationExtensionsPRFValuesOutput,
+    AuthenticatorAssertionResponse, AuthenticatorAttestationResponse, AuthenticatorSelectionCriteria,
+    PublicKeyCredential, PublicKeyCredentialCreationOptions, PublicKeyCredentialDescriptor,
+    PublicKeyCredentialRequestOptions,
 };
 
 use base64::Engine; // bring the `encode` method into scope
@@ -31,38 +42,50 @@ use base64::Engine; // bring the `encode` method into scope
 // -------------------------------------------------------------------------------------------------
 
 /// Register a new credential ("makeCredential" in WebAuthn).
-///
-/// This implementation is *synchronous* but runs inside an autorelease‑pool.
-pub fn create_credential_impl(opts: PublicKeyCredentialCreationOptions) -> Result<PublicKeyCredential> {
-    autoreleasepool(|| create_credential_inner(opts))
+pub async fn create_credential_impl(opts: PublicKeyCredentialCreationOptions) -> Result<PublicKeyCredential> {
+    let request = autoreleasepool(|| build_registration_request(&opts))?;
+    let credential = perform_authorization_request(request).await?;
+    autoreleasepool(|| parse_attestation_result(credential, opts.extensions.as_ref(), &opts.authenticator_selection))
 }
 
 /// Get an assertion from an existing credential ("getAssertion" in WebAuthn).
-pub fn get_credential_impl(opts: PublicKeyCredentialRequestOptions) -> Result<PublicKeyCredential> {
-    autoreleasepool(|| get_credential_inner(opts))
+pub async fn get_credential_impl(opts: PublicKeyCredentialRequestOptions) -> Result<PublicKeyCredential> {
+    let request = autoreleasepool(|| build_assertion_request(&opts))?;
+    let credential = perform_authorization_request(request).await?;
+    autoreleasepool(|| parse_assertion_result(credential, opts.extensions.as_ref()))
+}
+
+/// Whether this machine can service `create`/`get`: requires
+/// `ASAuthorizationPlatformPublicKeyCredentialProvider` (macOS 13+).
+pub async fn is_supported_impl() -> Result<bool> {
+    Ok(unsafe { Class::get("ASAuthorizationPlatformPublicKeyCredentialProvider").is_some() } && macos_major_version() >= 13)
+}
+
+unsafe fn macos_major_version() -> i64 {
+    let proc_cls = class!(NSProcessInfo);
+    let proc: *mut Object = msg_send![proc_cls, processInfo];
+    let version: *mut Object = msg_send![proc, operatingSystemVersion];
+    msg_send![version, majorVersion]
 }
 
 // -------------------------------------------------------------------------------------------------
-//  Private helpers – minimal, blocking implementation with a mock happy‑path.
+//  Request construction
 // -------------------------------------------------------------------------------------------------
 
-fn create_credential_inner(opts: PublicKeyCredentialCreationOptions) -> Result<PublicKeyCredential> {
+fn build_registration_request(opts: &PublicKeyCredentialCreationOptions) -> Result<StrongPtr> {
     unsafe {
-        // ── 1. Availability check ────────────────────────────────────────────────────────────────
         let provider_cls = Class::get("ASAuthorizationPlatformPublicKeyCredentialProvider").ok_or_else(||
-            Error::new(Status::GenericFailure, "ASAuthorizationPlatformPublicKeyCredentialProvider not available (needs macOS 13+)")
+            Error::new(Status::GenericFailure, "ASAuthorizationPlatformPublicKeyCredentialProvider not available (needs macOS 13+)")
         )?;
 
-        // ── 2. Build provider ────────────────────────────────────────────────────────────────────
         let rp_id = opts.rp.id.as_deref().ok_or_else(|| Error::new(Status::InvalidArg, "RP ID is required"))?;
-        let rp_ns  = NSString::from_str(rp_id);
+        let rp_ns = NSString::from_str(rp_id);
         let provider: *mut Object = msg_send![provider_cls, alloc];
         let provider: *mut Object = msg_send![provider, initWithRelyingPartyIdentifier: rp_ns];
 
-        // ── 3. Build request object ──────────────────────────────────────────────────────────────
-        let challenge   = NSData::with_bytes(&opts.challenge);
-        let user_id     = NSData::with_bytes(&opts.user.id);
-        let user_name   = NSString::from_str(&opts.user.name);
+        let challenge = NSData::with_bytes(&opts.challenge);
+        let user_id = NSData::with_bytes(&opts.user.id);
+        let user_name = NSString::from_str(&opts.user.name);
 
         let request: *mut Object = msg_send![provider,
             createCredentialRegistrationRequestWithChallenge: challenge
@@ -70,138 +93,414 @@ fn create_credential_inner(opts: PublicKeyCredentialCreationOptions) -> Result<P
             userID: user_id
         ];
 
-        // Optional display name (skip if empty to avoid invalid selector call)
         if !opts.user.display_name.is_empty() {
             let display = NSString::from_str(&opts.user.display_name);
             let _: () = msg_send![request, setDisplayName: display];
         }
 
-        // Map user‑verification preference, if present
         if let Some(sel) = opts.authenticator_selection.as_ref().and_then(|s| s.user_verification.as_ref()) {
-            let choice = match sel.as_str() {
-                "required" => "required",
-                "discouraged" => "discouraged",
-                _ => "preferred",
-            };
-            let choice_ns = NSString::from_str(choice);
+            let choice_ns = NSString::from_str(user_verification_choice(sel));
             let _: () = msg_send![request, setUserVerificationPreference: choice_ns];
         }
 
-        // ── 4. Perform the authorization (mocked) ────────────────────────────────────────────────
-        let credential = perform_authorization_request(vec![request])?;
-        parse_attestation_result(credential)
+        if let Some(exclude) = opts.exclude_credentials.as_ref().filter(|list| !list.is_empty()) {
+            let descriptors = build_credential_descriptors(exclude)?;
+            let array = nsarray_of(&descriptors);
+            let _: () = msg_send![request, setExcludedCredentials: array];
+        }
+
+        if let Some(eval) = opts.extensions.as_ref().and_then(|e| e.prf.as_ref()).and_then(|p| p.eval.as_ref()) {
+            let prf_input = build_prf_registration_input(eval);
+            let _: () = msg_send![request, setPrfRegistrationInput: prf_input];
+        }
+
+        Ok(StrongPtr::retain(request))
     }
 }
 
-fn get_credential_inner(opts: PublicKeyCredentialRequestOptions) -> Result<PublicKeyCredential> {
+fn build_assertion_request(opts: &PublicKeyCredentialRequestOptions) -> Result<StrongPtr> {
     unsafe {
-        // ── 1. Availability check ────────────────────────────────────────────────────────────────
         let provider_cls = Class::get("ASAuthorizationPlatformPublicKeyCredentialProvider").ok_or_else(||
-            Error::new(Status::GenericFailure, "ASAuthorizationPlatformPublicKeyCredentialProvider not available (needs macOS 13+)")
+            Error::new(Status::GenericFailure, "ASAuthorizationPlatformPublicKeyCredentialProvider not available (needs macOS 13+)")
         )?;
 
-        // ── 2. Build provider ────────────────────────────────────────────────────────────────────
         let rp_id = opts.rp_id.as_deref().ok_or_else(|| Error::new(Status::InvalidArg, "RP ID is required"))?;
-        let rp_ns  = NSString::from_str(rp_id);
+        let rp_ns = NSString::from_str(rp_id);
         let provider: *mut Object = msg_send![provider_cls, alloc];
         let provider: *mut Object = msg_send![provider, initWithRelyingPartyIdentifier: rp_ns];
 
-        // ── 3. Build request object ──────────────────────────────────────────────────────────────
         let challenge = NSData::with_bytes(&opts.challenge);
         let request: *mut Object = msg_send![provider, createCredentialAssertionRequestWithChallenge: challenge];
 
-        // Map user‑verification preference, if present
         if let Some(pref) = opts.user_verification.as_ref() {
-            let pref_ns = NSString::from_str(match pref.as_str() {
-                "required" => "required",
-                "discouraged" => "discouraged",
-                _ => "preferred",
-            });
+            let pref_ns = NSString::from_str(user_verification_choice(pref));
             let _: () = msg_send![request, setUserVerificationPreference: pref_ns];
         }
 
-        // TODO: allow_credentials → NSArray<NSData *> (skipped for now)
+        if let Some(allow) = opts.allow_credentials.as_ref().filter(|list| !list.is_empty()) {
+            let descriptors = build_credential_descriptors(allow)?;
+            let array = nsarray_of(&descriptors);
+            let _: () = msg_send![request, setAllowedCredentials: array];
+        }
+
+        if let Some(prf) = opts.extensions.as_ref().and_then(|e| e.prf.as_ref()) {
+            let prf_input = build_prf_assertion_input(prf);
+            let _: () = msg_send![request, setPrfInput: prf_input];
+        }
+
+        Ok(StrongPtr::retain(request))
+    }
+}
+
+/// Build an `ASAuthorizationPublicKeyCredentialPRFRegistrationInput` carrying the
+/// `eval` salts (first, plus second when given) the caller asked macOS to evaluate
+/// at creation time.
+unsafe fn build_prf_registration_input(eval: &AuthenticationExtensionsPRFValues) -> *mut Object {
+    let input_cls = class!(ASAuthorizationPublicKeyCredentialPRFRegistrationInput);
+    let values = build_prf_values(eval);
+    msg_send![input_cls, inputWithInputValues: values]
+}
+
+/// Build an `ASAuthorizationPublicKeyCredentialPRFAssertionInput` carrying the
+/// unconditional `eval` salts, plus a per‑credential `evalByCredential` map keyed
+/// by base64url credential ID, matching the WebAuthn PRF extension's semantics.
+unsafe fn build_prf_assertion_input(prf: &AuthenticationExtensionsPRFInputs) -> *mut Object {
+    let input_cls = class!(ASAuthorizationPublicKeyCredentialPRFAssertionInput);
+
+    let default_values = prf.eval.as_ref().map(|eval| build_prf_values(eval)).unwrap_or(std::ptr::null_mut());
+
+    let per_credential_dict: *mut Object = msg_send![class!(NSMutableDictionary), new];
+    if let Some(by_credential) = prf.eval_by_credential.as_ref() {
+        for (cred_id_b64, values) in by_credential {
+            // `perCredentialInputValues` is keyed by the credential's raw `NSData` ID, not
+            // the base64url string we're handed the extension input in.
+            let Ok(cred_id) = base64::engine::general_purpose::URL_SAFE_NO_PAD.decode(cred_id_b64) else {
+                continue;
+            };
+            let key = NSData::with_bytes(&cred_id);
+            let values_obj = build_prf_values(values);
+            let _: () = msg_send![per_credential_dict, setObject: values_obj forKey: key];
+        }
+    }
+
+    msg_send![input_cls, inputWithInputValues: default_values perCredentialInputValues: per_credential_dict]
+}
+
+unsafe fn build_prf_values(values: &AuthenticationExtensionsPRFValues) -> *mut Object {
+    let values_cls = class!(ASAuthorizationPublicKeyCredentialPRFAssertionInputValues);
+    let salt1 = NSData::with_bytes(&values.first);
+    let obj: *mut Object = msg_send![values_cls, alloc];
+    match values.second.as_ref() {
+        Some(second) => {
+            let salt2 = NSData::with_bytes(second);
+            msg_send![obj, initWithSaltInput1: salt1 saltInput2: salt2]
+        }
+        None => msg_send![obj, initWithSaltInput1: salt1],
+    }
+}
+
+/// Map WebAuthn credential descriptors to
+/// `ASAuthorizationPlatformPublicKeyCredentialDescriptor` objects. Platform
+/// credentials are keyed purely by credential ID, so `transports` (which only
+/// applies to cross‑platform/security‑key descriptors) is intentionally
+/// dropped here.
+unsafe fn build_credential_descriptors(descriptors: &[PublicKeyCredentialDescriptor]) -> Result<Vec<StrongPtr>> {
+    let descriptor_cls = Class::get("ASAuthorizationPlatformPublicKeyCredentialDescriptor").ok_or_else(|| {
+        Error::new(Status::GenericFailure, "ASAuthorizationPlatformPublicKeyCredentialDescriptor not available (needs macOS 13+)")
+    })?;
+
+    descriptors
+        .iter()
+        .map(|descriptor| {
+            let id_data = NSData::with_bytes(&descriptor.id);
+            let obj: *mut Object = msg_send![descriptor_cls, alloc];
+            let obj: *mut Object = msg_send![obj, initWithCredentialID: id_data];
+            Ok(StrongPtr::retain(obj))
+        })
+        .collect()
+}
+
+unsafe fn nsarray_of(objects: &[StrongPtr]) -> *mut Object {
+    let ptrs: Vec<*mut Object> = objects.iter().map(|o| o.as_ptr()).collect();
+    msg_send![class!(NSArray), arrayWithObjects: ptrs.as_ptr() count: ptrs.len()]
+}
 
-        // ── 4. Perform the authorization (mocked) ────────────────────────────────────────────────
-        let credential = perform_authorization_request(vec![request])?;
-        parse_assertion_result(credential)
+fn user_verification_choice(preference: &str) -> &'static str {
+    match preference {
+        "required" => "required",
+        "discouraged" => "discouraged",
+        _ => "preferred",
     }
 }
 
 // -------------------------------------------------------------------------------------------------
-//  Minimal stand‑in for an ASAuthorizationController round‑trip.
-//  Replace with real async delegate when integrating with UI.
+//  ASAuthorizationController round‑trip, bridged to `async` via a delegate + oneshot channel.
 // -------------------------------------------------------------------------------------------------
 
-unsafe fn perform_authorization_request(_requests: Vec<*mut Object>) -> Result<*mut Object> {
+type AuthOutcome = std::result::Result<StrongPtr, String>;
+
+async fn perform_authorization_request(request: StrongPtr) -> Result<StrongPtr> {
     // Basic runtime version gate (major >= 13)
-    let proc_cls = Class::get("NSProcessInfo").unwrap();
-    let proc: *mut Object = msg_send![proc_cls, processInfo];
-    let version: *mut Object = msg_send![proc, operatingSystemVersion];
-    let major: i64 = msg_send![version, majorVersion];
-    if major < 13 {
-        return Err(Error::new(Status::GenericFailure, "macOS 13 (Ventura) or later required"));
+    if unsafe { macos_major_version() } < 13 {
+        return Err(Error::new(Status::GenericFailure, "macOS 13 (Ventura) or later required"));
     }
 
-    // Mock success path – create a dummy NSObject so downstream code can
-    // pretend it received an ASAuthorization credential.
-    let nsobj_cls = Class::get("NSObject").unwrap();
-    let obj: *mut Object = msg_send![nsobj_cls, new];
-    Ok(obj)
+    let (tx, rx) = oneshot::channel::<AuthOutcome>();
+
+    // ASAuthorizationController (and its delegate callbacks) must be driven on the main
+    // thread/run loop, so we dispatch construction + `performRequests` there and let the
+    // delegate wake the oneshot channel once macOS calls back.
+    let sender_ptr = Box::into_raw(Box::new(Some(tx))) as *mut c_void as usize;
+    let request_ptr = request.as_ptr() as usize;
+
+    dispatch_on_main(move || unsafe {
+        let array_cls = class!(NSArray);
+        let requests: *mut Object = msg_send![array_cls, arrayWithObject: request_ptr as *mut Object];
+
+        let controller_cls = Class::get("ASAuthorizationController").expect("ASAuthorizationController unavailable");
+        let controller: *mut Object = msg_send![controller_cls, alloc];
+        let controller: *mut Object = msg_send![controller, initWithAuthorizationRequests: requests];
+
+        let delegate = delegate_instance(sender_ptr as *mut c_void);
+        let _: () = msg_send![controller, setDelegate: delegate];
+        let _: () = msg_send![controller, setPresentationContextProvider: delegate];
+        // `controller` and `delegate` are retained by the Objective‑C runtime for the
+        // duration of the request via the (implicitly strong) delegate properties above;
+        // `performRequests` kicks off the actual OS UI.
+        let _: () = msg_send![controller, performRequests];
+    });
+
+    match rx.await {
+        Ok(Ok(credential)) => Ok(credential),
+        Ok(Err(message)) => Err(Error::new(Status::GenericFailure, message)),
+        Err(_) => Err(Error::new(Status::GenericFailure, "authorization delegate was dropped before completing")),
+    }
+}
+
+/// Run `f` on the main thread/run loop and return immediately; macOS UI APIs
+/// (including `ASAuthorizationController`) require this.
+fn dispatch_on_main<F: FnOnce() + Send + 'static>(f: F) {
+    unsafe {
+        if Class::get("NSThread").is_some() {
+            let is_main: bool = msg_send![class!(NSThread), isMainThread];
+            if is_main {
+                f();
+                return;
+            }
+        }
+        dispatch::Queue::main().exec_async(f);
+    }
 }
 
 // -------------------------------------------------------------------------------------------------
-//  Result parsing helpers (mock data)
+//  Delegate: ASAuthorizationControllerDelegate + ASAuthorizationControllerPresentationContextProviding
 // -------------------------------------------------------------------------------------------------
 
-unsafe fn parse_attestation_result(_credential: *mut Object) -> Result<PublicKeyCredential> {
-    // Mock credential‑id bytes (16 random bytes)
-    let cred_id_bytes: [u8; 16] = [
-        0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0,
-        0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88,
-    ];
+static REGISTER_DELEGATE_CLASS: Once = Once::new();
+
+fn delegate_class() -> &'static Class {
+    REGISTER_DELEGATE_CLASS.call_once(|| unsafe {
+        let superclass = class!(NSObject);
+        let mut decl = ClassDecl::new("ElectronWebauthnAuthorizationDelegate", superclass)
+            .expect("failed to declare ElectronWebauthnAuthorizationDelegate");
+
+        decl.add_ivar::<*mut c_void>("_senderBox");
+
+        decl.add_method(
+            sel!(authorizationController:didCompleteWithAuthorization:),
+            did_complete_with_authorization as extern "C" fn(&Object, Sel, *mut Object, *mut Object),
+        );
+        decl.add_method(
+            sel!(authorizationController:didCompleteWithError:),
+            did_complete_with_error as extern "C" fn(&Object, Sel, *mut Object, *mut Object),
+        );
+        decl.add_method(
+            sel!(presentationAnchorForAuthorizationController:),
+            presentation_anchor as extern "C" fn(&Object, Sel, *mut Object) -> *mut Object,
+        );
+
+        decl.register();
+    });
+
+    Class::get("ElectronWebauthnAuthorizationDelegate").expect("delegate class was not registered")
+}
 
-    let client_data_json = b"{\"type\":\"webauthn.create\",\"challenge\":\"mock\",\"origin\":\"https://example.com\"}".to_vec();
-    let att_object       = vec![0xA3, 0x63, 0x66, 0x6D, 0x74, 0x64]; // undersized CBOR – ok for mock
+/// Allocate a delegate instance that owns (and, on completion, consumes) `sender`.
+unsafe fn delegate_instance(sender: *mut c_void) -> *mut Object {
+    let cls = delegate_class();
+    let delegate: *mut Object = msg_send![cls, alloc];
+    let delegate: *mut Object = msg_send![delegate, init];
+    (*delegate).set_ivar::<*mut c_void>("_senderBox", sender);
+    delegate
+}
+
+unsafe fn take_sender(this: &Object) -> Option<oneshot::Sender<AuthOutcome>> {
+    let boxed = *this.get_ivar::<*mut c_void>("_senderBox");
+    if boxed.is_null() {
+        return None;
+    }
+    let mut sender_box = Box::from_raw(boxed as *mut Option<oneshot::Sender<AuthOutcome>>);
+    sender_box.take()
+}
+
+extern "C" fn did_complete_with_authorization(this: &Object, _sel: Sel, _controller: *mut Object, authorization: *mut Object) {
+    unsafe {
+        if let Some(sender) = take_sender(this) {
+            let credential: *mut Object = msg_send![authorization, credential];
+            let _ = sender.send(Ok(StrongPtr::retain(credential)));
+        }
+    }
+}
+
+extern "C" fn did_complete_with_error(this: &Object, _sel: Sel, _controller: *mut Object, error: *mut Object) {
+    unsafe {
+        if let Some(sender) = take_sender(this) {
+            let description: *mut Object = msg_send![error, localizedDescription];
+            let message = description_to_string(description);
+            let _ = sender.send(Err(message));
+        }
+    }
+}
+
+extern "C" fn presentation_anchor(_this: &Object, _sel: Sel, _controller: *mut Object) -> *mut Object {
+    unsafe {
+        let app_cls = class!(NSApplication);
+        let app: *mut Object = msg_send![app_cls, sharedApplication];
+        let window: *mut Object = msg_send![app, keyWindow];
+        if !window.is_null() {
+            return window;
+        }
+        msg_send![app, mainWindow]
+    }
+}
+
+unsafe fn description_to_string(ns_string: *mut Object) -> String {
+    if ns_string.is_null() {
+        return "authorization failed".to_string();
+    }
+    let utf8: *const std::os::raw::c_char = msg_send![ns_string, UTF8String];
+    if utf8.is_null() {
+        return "authorization failed".to_string();
+    }
+    std::ffi::CStr::from_ptr(utf8).to_string_lossy().into_owned()
+}
+
+// -------------------------------------------------------------------------------------------------
+//  Result parsing helpers
+// -------------------------------------------------------------------------------------------------
+
+unsafe fn parse_attestation_result(
+    credential: StrongPtr,
+    extensions: Option<&AuthenticationExtensionsClientInputs>,
+    authenticator_selection: &Option<AuthenticatorSelectionCriteria>,
+) -> Result<PublicKeyCredential> {
+    let credential = credential.as_ptr();
+
+    let raw_client_data: *mut Object = msg_send![credential, rawClientDataJSON];
+    let raw_attestation_object: *mut Object = msg_send![credential, rawAttestationObject];
+    let cred_id: *mut Object = msg_send![credential, credentialID];
+
+    let client_data_json = nsdata_to_vec(raw_client_data);
+    let attestation_object = nsdata_to_vec(raw_attestation_object);
+    let cred_id_bytes = nsdata_to_vec(cred_id);
 
     let att_response = AuthenticatorAttestationResponse {
         client_data_json: Buffer::from(client_data_json),
-        attestation_object: Buffer::from(att_object),
+        attestation_object: Buffer::from(attestation_object),
         transports: vec!["internal".to_string()],
     };
 
+    let wants_prf = extensions.and_then(|e| e.prf.as_ref()).is_some();
+    let wants_cred_props = extensions.and_then(|e| e.cred_props).unwrap_or(false);
+
+    let client_extension_results = AuthenticationExtensionsClientOutputs {
+        appid: None,
+        appid_exclude: None,
+        cred_props: wants_cred_props.then(|| AuthenticationExtensionsCredentialPropertiesOutput {
+            rk: authenticator_selection.as_ref().and_then(|s| s.require_resident_key),
+        }),
+        prf: wants_prf.then(|| read_prf_registration_output(credential)).flatten(),
+    };
+
     Ok(PublicKeyCredential {
         id: base64_url_encode(&cred_id_bytes),
-        raw_id: Buffer::from(&cred_id_bytes[..]),
+        raw_id: Buffer::from(cred_id_bytes),
         response: Either::A(att_response),
         authenticator_attachment: Some("platform".to_string()),
         type_: "public-key".to_string(),
+        client_extension_results,
     })
 }
 
-unsafe fn parse_assertion_result(_credential: *mut Object) -> Result<PublicKeyCredential> {
-    let cred_id_bytes: [u8; 16] = [
-        0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0,
-        0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88,
-    ];
+unsafe fn parse_assertion_result(
+    credential: StrongPtr,
+    extensions: Option<&AuthenticationExtensionsClientInputs>,
+) -> Result<PublicKeyCredential> {
+    let credential = credential.as_ptr();
+
+    let raw_client_data: *mut Object = msg_send![credential, rawClientDataJSON];
+    let raw_authenticator_data: *mut Object = msg_send![credential, rawAuthenticatorData];
+    let raw_signature: *mut Object = msg_send![credential, signature];
+    let cred_id: *mut Object = msg_send![credential, credentialID];
+    let user_id: *mut Object = msg_send![credential, userID];
 
-    let client_data_json = b"{\"type\":\"webauthn.get\",\"challenge\":\"mock\",\"origin\":\"https://example.com\"}".to_vec();
-    let auth_data        = vec![0x49, 0x96, 0x0D, 0xE5];
-    let signature        = vec![0x30, 0x45, 0x02, 0x20];
+    let client_data_json = nsdata_to_vec(raw_client_data);
+    let authenticator_data = nsdata_to_vec(raw_authenticator_data);
+    let signature = nsdata_to_vec(raw_signature);
+    let cred_id_bytes = nsdata_to_vec(cred_id);
+    let user_handle = if user_id.is_null() { None } else { Some(Buffer::from(nsdata_to_vec(user_id))) };
 
     let assert_response = AuthenticatorAssertionResponse {
         client_data_json: Buffer::from(client_data_json),
-        authenticator_data: Buffer::from(auth_data),
+        authenticator_data: Buffer::from(authenticator_data),
         signature: Buffer::from(signature),
-        user_handle: None,
+        user_handle,
+    };
+
+    let wants_prf = extensions.and_then(|e| e.prf.as_ref()).is_some();
+    let client_extension_results = AuthenticationExtensionsClientOutputs {
+        appid: None,
+        appid_exclude: None,
+        cred_props: None,
+        prf: wants_prf.then(|| read_prf_assertion_output(credential)).flatten(),
     };
 
     Ok(PublicKeyCredential {
         id: base64_url_encode(&cred_id_bytes),
-        raw_id: Buffer::from(&cred_id_bytes[..]),
+        raw_id: Buffer::from(cred_id_bytes),
         response: Either::B(assert_response),
         authenticator_attachment: Some("platform".to_string()),
         type_: "public-key".to_string(),
+        client_extension_results,
+    })
+}
+
+/// Read back `ASAuthorizationPlatformPublicKeyCredentialRegistration.prfRegistrationOutput`.
+/// Only reports whether PRF is supported for this credential (`enabled`); any salt
+/// outputs macOS evaluated at creation time are not read back, so `results` is `None`.
+unsafe fn read_prf_registration_output(credential: *mut Object) -> Option<AuthenticationExtensionsPRFOutputs> {
+    let output: *mut Object = msg_send![credential, prfRegistrationOutput];
+    if output.is_null() {
+        return None;
+    }
+    let enabled: bool = msg_send![output, isSupported];
+    Some(AuthenticationExtensionsPRFOutputs { enabled: Some(enabled), results: None })
+}
+
+/// Read back `ASAuthorizationPlatformPublicKeyCredentialAssertion.prfOutput`.
+unsafe fn read_prf_assertion_output(credential: *mut Object) -> Option<AuthenticationExtensionsPRFOutputs> {
+    let output: *mut Object = msg_send![credential, prfOutput];
+    if output.is_null() {
+        return None;
+    }
+    let first: *mut Object = msg_send![output, first];
+    let second: *mut Object = msg_send![output, second];
+    Some(AuthenticationExtensionsPRFOutputs {
+        enabled: None,
+        results: Some(AuthenticationExtensionsPRFValuesOutput {
+            first: Buffer::from(nsdata_to_vec(first)),
+            second: if second.is_null() { None } else { Some(Buffer::from(nsdata_to_vec(second))) },
+        }),
     })
 }
 
@@ -209,6 +508,18 @@ unsafe fn parse_assertion_result(_credential: *mut Object) -> Result<PublicKeyCr
 //  Utility helpers
 // -------------------------------------------------------------------------------------------------
 
+unsafe fn nsdata_to_vec(data: *mut Object) -> Vec<u8> {
+    if data.is_null() {
+        return Vec::new();
+    }
+    let len: usize = msg_send![data, length];
+    let bytes: *const u8 = msg_send![data, bytes];
+    if bytes.is_null() || len == 0 {
+        return Vec::new();
+    }
+    std::slice::from_raw_parts(bytes, len).to_vec()
+}
+
 fn base64_url_encode(data: &[u8]) -> String {
     base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data)
 }
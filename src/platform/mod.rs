@@ -6,19 +6,60 @@ mod macos;
 #[cfg(target_os = "macos")]
 use macos::{create_credential_impl, get_credential_impl, is_supported_impl};
 
-#[cfg(not(any(target_os = "macos")))]
+#[cfg(target_os = "windows")]
+mod windows;
+#[cfg(target_os = "windows")]
+use windows::{create_credential_impl, get_credential_impl, is_supported_impl};
+
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
 mod unknown;
-#[cfg(not(any(target_os = "macos")))]
+#[cfg(not(any(target_os = "macos", target_os = "windows")))]
 use unknown::{create_credential_impl, get_credential_impl, is_supported_impl};
 
+#[cfg(feature = "software-authenticator")]
+mod software;
+
+mod soft_credential;
+
+pub mod virtual_authenticator;
+
+/// Force the in‑process software authenticator on for the lifetime of the
+/// process, bypassing the OS backend entirely. No‑op without the
+/// `software-authenticator` feature.
+pub fn use_software_authenticator() {
+    #[cfg(feature = "software-authenticator")]
+    software::enable();
+}
+
 pub async fn create_credential(options: PublicKeyCredentialCreationOptions) -> Result<PublicKeyCredential> {
+    if virtual_authenticator::has_any() {
+        return virtual_authenticator::create_credential_impl(options).await;
+    }
+    #[cfg(feature = "software-authenticator")]
+    if software::is_enabled() {
+        return software::create_credential_impl(options).await;
+    }
     create_credential_impl(options).await
 }
 
 pub async fn get_credential(options: PublicKeyCredentialRequestOptions) -> Result<PublicKeyCredential> {
+    if virtual_authenticator::has_any() {
+        return virtual_authenticator::get_credential_impl(options).await;
+    }
+    #[cfg(feature = "software-authenticator")]
+    if software::is_enabled() {
+        return software::get_credential_impl(options).await;
+    }
     get_credential_impl(options).await
-} 
+}
 
 pub async fn is_supported() -> Result<bool> {
+    if virtual_authenticator::has_any() {
+        return Ok(true);
+    }
+    #[cfg(feature = "software-authenticator")]
+    if software::is_enabled() {
+        return Ok(true);
+    }
     is_supported_impl().await
 }
\ No newline at end of file
@@ -0,0 +1,186 @@
+//! Shared authenticatorData / CBOR attestation / COSE key encoding used by the
+//! in‑process credential backends (`software.rs`, `virtual_authenticator.rs`).
+//! Kept separate from either backend so the wire-format logic has exactly one
+//! implementation instead of two independently-maintained copies.
+
+use ciborium::value::Value;
+use p256::ecdsa::VerifyingKey;
+use sha2::{Digest, Sha256};
+
+pub const AAGUID: [u8; 16] = [0u8; 16];
+
+pub fn build_authenticator_data(rp_id: &str, user_verified: bool, sign_count: u32, attested_credential_data: Option<&[u8]>) -> Vec<u8> {
+    let mut data = Vec::new();
+    data.extend_from_slice(&Sha256::digest(rp_id.as_bytes()));
+
+    let mut flags = 0x01; // UP
+    if user_verified {
+        flags |= 0x04; // UV
+    }
+    if attested_credential_data.is_some() {
+        flags |= 0x40; // AT
+    }
+    data.push(flags);
+
+    data.extend_from_slice(&sign_count.to_be_bytes());
+    if let Some(acd) = attested_credential_data {
+        data.extend_from_slice(acd);
+    }
+    data
+}
+
+pub fn encode_attestation_object(auth_data: &[u8]) -> Vec<u8> {
+    let value = Value::Map(vec![
+        (Value::Text("fmt".to_string()), Value::Text("none".to_string())),
+        (Value::Text("attStmt".to_string()), Value::Map(vec![])),
+        (Value::Text("authData".to_string()), Value::Bytes(auth_data.to_vec())),
+    ]);
+
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(&value, &mut bytes).expect("cbor encoding of attestation object cannot fail");
+    bytes
+}
+
+pub fn encode_cose_p256_key(key: &VerifyingKey) -> Vec<u8> {
+    let point = key.to_encoded_point(false);
+    let x = point.x().expect("uncompressed point has an x coordinate").to_vec();
+    let y = point.y().expect("uncompressed point has a y coordinate").to_vec();
+
+    let value = Value::Map(vec![
+        (Value::Integer(1.into()), Value::Integer(2.into())),     // kty: EC2
+        (Value::Integer(3.into()), Value::Integer((-7).into())),  // alg: ES256
+        (Value::Integer((-1).into()), Value::Integer(1.into())),  // crv: P-256
+        (Value::Integer((-2).into()), Value::Bytes(x)),           // x
+        (Value::Integer((-3).into()), Value::Bytes(y)),           // y
+    ]);
+
+    let mut bytes = Vec::new();
+    ciborium::ser::into_writer(&value, &mut bytes).expect("cbor encoding of COSE key cannot fail");
+    bytes
+}
+
+pub fn build_client_data_json(ty: &str, challenge: &[u8]) -> Vec<u8> {
+    format!(
+        "{{\"type\":\"{}\",\"challenge\":\"{}\",\"origin\":\"https://example.com\",\"crossOrigin\":false}}",
+        ty,
+        base64_url_encode(challenge)
+    )
+    .into_bytes()
+}
+
+pub fn base64_url_encode(data: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data)
+}
+
+// Exercised here rather than in `software.rs`/`virtual_authenticator.rs`: those
+// modules hand back napi `Buffer`s, whose `Drop` impl calls into real N-API
+// functions that only exist once this addon is loaded by Node, so a `cargo
+// test` binary (which never loads Node) fails to link against them. This
+// module never touches napi types, so it is the one place a `create`/`get`
+// wire-format round trip — authenticatorData layout, COSE key encoding, CBOR
+// attestation object, and the ECDSA signature the backends produce over it —
+// can actually run under `cargo test`.
+#[cfg(test)]
+mod tests {
+    use p256::ecdsa::signature::{Signer, Verifier};
+    use p256::ecdsa::{Signature, SigningKey, VerifyingKey};
+    use p256::elliptic_curve::rand_core::OsRng;
+    use p256::{EncodedPoint, FieldBytes};
+
+    use super::*;
+
+    fn decode_cose_p256_key(cose_key: &[u8]) -> VerifyingKey {
+        let value: Value = ciborium::de::from_reader(cose_key).unwrap();
+        let map = match value {
+            Value::Map(entries) => entries,
+            other => panic!("expected a CBOR map for the COSE key, got {other:?}"),
+        };
+        let coordinate = |label: i64| -> FieldBytes {
+            let bytes: [u8; 32] = map
+                .iter()
+                .find_map(|(k, v)| match (k.as_integer().and_then(|i| i64::try_from(i).ok()), v) {
+                    (Some(i), Value::Bytes(bytes)) if i == label => Some(bytes.clone()),
+                    _ => None,
+                })
+                .unwrap_or_else(|| panic!("COSE key missing label {label}"))
+                .try_into()
+                .unwrap_or_else(|_| panic!("COSE key coordinate {label} was not 32 bytes"));
+            FieldBytes::from(bytes)
+        };
+        let point = EncodedPoint::from_affine_coordinates(&coordinate(-2), &coordinate(-3), false);
+        VerifyingKey::from_encoded_point(&point).unwrap()
+    }
+
+    #[test]
+    fn cose_key_round_trips_through_cbor() {
+        let signing_key = SigningKey::random(&mut OsRng);
+        let verifying_key = VerifyingKey::from(&signing_key);
+
+        let cose_key = encode_cose_p256_key(&verifying_key);
+        let decoded = decode_cose_p256_key(&cose_key);
+
+        assert_eq!(decoded, verifying_key);
+    }
+
+    #[test]
+    fn authenticator_data_has_expected_layout_and_flags() {
+        let rp_id = "example.com";
+        let cred_id = vec![0xAB; 16];
+        let cose_key = encode_cose_p256_key(&VerifyingKey::from(&SigningKey::random(&mut OsRng)));
+
+        let mut attested_credential_data = Vec::new();
+        attested_credential_data.extend_from_slice(&AAGUID);
+        attested_credential_data.extend_from_slice(&(cred_id.len() as u16).to_be_bytes());
+        attested_credential_data.extend_from_slice(&cred_id);
+        attested_credential_data.extend_from_slice(&cose_key);
+
+        let registration = build_authenticator_data(rp_id, true, 0, Some(&attested_credential_data));
+        let rp_id_hash: Vec<u8> = Sha256::digest(rp_id.as_bytes()).to_vec();
+        assert_eq!(&registration[0..32], rp_id_hash.as_slice(), "rpIdHash");
+        assert_eq!(registration[32], 0x01 | 0x04 | 0x40, "UP | UV | AT flags");
+        assert_eq!(&registration[33..37], &0u32.to_be_bytes(), "initial signCount");
+        assert_eq!(&registration[37..], attested_credential_data.as_slice(), "attestedCredentialData");
+
+        let assertion = build_authenticator_data(rp_id, false, 7, None);
+        assert_eq!(assertion.len(), 37, "no attestedCredentialData on a getAssertion");
+        assert_eq!(assertion[32], 0x01, "UP only, no UV/AT");
+        assert_eq!(&assertion[33..37], &7u32.to_be_bytes(), "signCount");
+    }
+
+    #[test]
+    fn attestation_object_round_trips_through_cbor() {
+        let auth_data = build_authenticator_data("example.com", true, 0, None);
+        let attestation_object = encode_attestation_object(&auth_data);
+
+        let value: Value = ciborium::de::from_reader(attestation_object.as_slice()).unwrap();
+        let map = match value {
+            Value::Map(entries) => entries,
+            other => panic!("expected a CBOR map attestation object, got {other:?}"),
+        };
+
+        let get = |key: &str| map.iter().find(|(k, _)| k.as_text() == Some(key)).map(|(_, v)| v.clone());
+        assert_eq!(get("fmt"), Some(Value::Text("none".to_string())));
+        assert_eq!(get("attStmt"), Some(Value::Map(vec![])));
+        assert_eq!(get("authData"), Some(Value::Bytes(auth_data)));
+    }
+
+    /// Mirrors what `software::get_credential_impl`/`virtual_authenticator::get_credential_impl`
+    /// do: sign `authenticatorData || sha256(clientDataJSON)` and verify it against the
+    /// credential's COSE-encoded public key.
+    #[test]
+    fn signature_over_authenticator_data_and_client_data_hash_verifies() {
+        let signing_key = SigningKey::random(&mut OsRng);
+        let verifying_key = decode_cose_p256_key(&encode_cose_p256_key(&VerifyingKey::from(&signing_key)));
+
+        let auth_data = build_authenticator_data("example.com", true, 1, None);
+        let client_data_json = build_client_data_json("webauthn.get", b"some-challenge");
+        let client_data_hash = Sha256::digest(&client_data_json);
+
+        let mut signed_payload = auth_data.clone();
+        signed_payload.extend_from_slice(&client_data_hash);
+        let signature: Signature = signing_key.sign(&signed_payload);
+
+        verifying_key.verify(&signed_payload, &signature).expect("signature must verify against the attested public key");
+    }
+}
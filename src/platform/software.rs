@@ -0,0 +1,145 @@
+//! In‑process software authenticator for CI and headless testing.
+//! ---------------------------------------------------------------------------
+//! Mirrors Firefox's `test_token.rs` soft authenticator: every `create`/`get`
+//! is serviced entirely in‑process with real P‑256 keys instead of talking to
+//! OS UI, so the crate (and anything built on top of it) can be exercised
+//! end‑to‑end in CI. Selected at runtime either by setting
+//! `ELECTRON_WEBAUTHN_SOFTWARE_AUTHENTICATOR=1` or by calling
+//! `use_software_authenticator()` from JS before the first `create`/`get`.
+
+#![cfg(feature = "software-authenticator")]
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use napi::{bindgen_prelude::*, Error, Result, Status};
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use p256::elliptic_curve::rand_core::{OsRng, RngCore};
+use sha2::{Digest, Sha256};
+
+use super::soft_credential::{
+    base64_url_encode, build_authenticator_data, build_client_data_json, encode_attestation_object, encode_cose_p256_key, AAGUID,
+};
+use crate::{
+    AuthenticationExtensionsClientOutputs, AuthenticatorAssertionResponse, AuthenticatorAttestationResponse,
+    PublicKeyCredential, PublicKeyCredentialCreationOptions, PublicKeyCredentialRequestOptions,
+};
+
+struct StoredCredential {
+    signing_key: SigningKey,
+    sign_count: u32,
+    user_handle: Vec<u8>,
+}
+
+type CredentialKey = (String, Vec<u8>);
+
+static FORCE_ENABLED: AtomicBool = AtomicBool::new(false);
+
+fn store() -> &'static Mutex<HashMap<CredentialKey, StoredCredential>> {
+    static STORE: OnceLock<Mutex<HashMap<CredentialKey, StoredCredential>>> = OnceLock::new();
+    STORE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Force the software authenticator on for the lifetime of the process, in
+/// addition to the `ELECTRON_WEBAUTHN_SOFTWARE_AUTHENTICATOR` env var.
+pub fn enable() {
+    FORCE_ENABLED.store(true, Ordering::SeqCst);
+}
+
+pub fn is_enabled() -> bool {
+    FORCE_ENABLED.load(Ordering::SeqCst)
+        || matches!(
+            std::env::var("ELECTRON_WEBAUTHN_SOFTWARE_AUTHENTICATOR").as_deref(),
+            Ok("1") | Ok("true")
+        )
+}
+
+pub async fn create_credential_impl(opts: PublicKeyCredentialCreationOptions) -> Result<PublicKeyCredential> {
+    let rp_id = opts.rp.id.clone().unwrap_or_else(|| opts.rp.name.clone());
+
+    let signing_key = SigningKey::random(&mut OsRng);
+    let verifying_key = VerifyingKey::from(&signing_key);
+
+    let mut cred_id = vec![0u8; 32];
+    OsRng.fill_bytes(&mut cred_id);
+
+    let cose_key = encode_cose_p256_key(&verifying_key);
+    let mut attested_credential_data = Vec::with_capacity(16 + 2 + cred_id.len() + cose_key.len());
+    attested_credential_data.extend_from_slice(&AAGUID);
+    attested_credential_data.extend_from_slice(&(cred_id.len() as u16).to_be_bytes());
+    attested_credential_data.extend_from_slice(&cred_id);
+    attested_credential_data.extend_from_slice(&cose_key);
+
+    let auth_data = build_authenticator_data(&rp_id, true, 0, Some(&attested_credential_data));
+    let attestation_object = encode_attestation_object(&auth_data);
+    let client_data_json = build_client_data_json("webauthn.create", &opts.challenge);
+
+    store().lock().unwrap().insert(
+        (rp_id, cred_id.clone()),
+        StoredCredential { signing_key, sign_count: 0, user_handle: opts.user.id.to_vec() },
+    );
+
+    let response = AuthenticatorAttestationResponse {
+        client_data_json: Buffer::from(client_data_json),
+        attestation_object: Buffer::from(attestation_object),
+        transports: vec!["internal".to_string()],
+    };
+
+    Ok(PublicKeyCredential {
+        id: base64_url_encode(&cred_id),
+        raw_id: Buffer::from(cred_id),
+        response: Either::A(response),
+        authenticator_attachment: Some("platform".to_string()),
+        type_: "public-key".to_string(),
+        client_extension_results: AuthenticationExtensionsClientOutputs::default(),
+    })
+}
+
+pub async fn get_credential_impl(opts: PublicKeyCredentialRequestOptions) -> Result<PublicKeyCredential> {
+    let rp_id = opts.rp_id.clone().ok_or_else(|| Error::new(Status::InvalidArg, "RP ID is required"))?;
+
+    let mut store = store().lock().unwrap();
+
+    let cred_id = match opts.allow_credentials.as_ref().filter(|list| !list.is_empty()) {
+        Some(allowed) => allowed
+            .iter()
+            .map(|descriptor| descriptor.id.to_vec())
+            .find(|id| store.contains_key(&(rp_id.clone(), id.clone())))
+            .ok_or_else(|| Error::new(Status::GenericFailure, "no matching software credential registered for this relying party"))?,
+        None => store
+            .keys()
+            .find(|(rp, _)| rp == &rp_id)
+            .map(|(_, id)| id.clone())
+            .ok_or_else(|| Error::new(Status::GenericFailure, "no software credential registered for this relying party"))?,
+    };
+
+    let credential = store.get_mut(&(rp_id.clone(), cred_id.clone())).expect("looked up above");
+    credential.sign_count += 1;
+    let auth_data = build_authenticator_data(&rp_id, true, credential.sign_count, None);
+
+    let client_data_json = build_client_data_json("webauthn.get", &opts.challenge);
+    let client_data_hash = Sha256::digest(&client_data_json);
+
+    let mut signed_payload = auth_data.clone();
+    signed_payload.extend_from_slice(&client_data_hash);
+    let signature: Signature = credential.signing_key.sign(&signed_payload);
+    let user_handle = credential.user_handle.clone();
+
+    let response = AuthenticatorAssertionResponse {
+        client_data_json: Buffer::from(client_data_json),
+        authenticator_data: Buffer::from(auth_data),
+        signature: Buffer::from(signature.to_der().as_bytes().to_vec()),
+        user_handle: Some(Buffer::from(user_handle)),
+    };
+
+    Ok(PublicKeyCredential {
+        id: base64_url_encode(&cred_id),
+        raw_id: Buffer::from(cred_id),
+        response: Either::B(response),
+        authenticator_attachment: Some("platform".to_string()),
+        type_: "public-key".to_string(),
+        client_extension_results: AuthenticationExtensionsClientOutputs::default(),
+    })
+}
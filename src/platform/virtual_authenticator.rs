@@ -0,0 +1,248 @@
+//! Virtual‑authenticator management, modeled on the WebDriver / `nsIWebAuthnTransport`
+//! virtual authenticator API. Lets test harnesses register scripted authenticators
+//! (`addVirtualAuthenticator`/`removeVirtualAuthenticator`/`addCredential`/
+//! `getCredentials`/`setUserVerified`) so Electron app automation can drive `create`/`get`
+//! deterministically. While one or more virtual authenticators are registered,
+//! `create`/`get` are serviced here instead of the OS backend.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+
+use napi::{bindgen_prelude::*, Error, Result, Status};
+use p256::ecdsa::signature::Signer;
+use p256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use p256::elliptic_curve::rand_core::{OsRng, RngCore};
+use p256::pkcs8::{DecodePrivateKey, EncodePrivateKey};
+use sha2::{Digest, Sha256};
+
+use super::soft_credential::{
+    base64_url_encode, build_authenticator_data, build_client_data_json, encode_attestation_object, encode_cose_p256_key, AAGUID,
+};
+use crate::{
+    AuthenticationExtensionsClientOutputs, AuthenticatorAssertionResponse, AuthenticatorAttestationResponse,
+    PublicKeyCredential, PublicKeyCredentialCreationOptions, PublicKeyCredentialRequestOptions,
+    VirtualAuthenticatorCredential, VirtualAuthenticatorOptions,
+};
+
+struct StoredCredential {
+    signing_key: SigningKey,
+    sign_count: u32,
+    user_handle: Option<Vec<u8>>,
+}
+
+struct VirtualAuthenticator {
+    options: VirtualAuthenticatorOptions,
+    user_verified: bool,
+    credentials: HashMap<(String, Vec<u8>), StoredCredential>,
+}
+
+/// Authenticators in insertion order (oldest first), so routing `create`/`get`
+/// to "the most recently added authenticator" is deterministic rather than at
+/// the mercy of `HashMap` iteration order.
+#[derive(Default)]
+struct Registry {
+    order: Vec<String>,
+    authenticators: HashMap<String, VirtualAuthenticator>,
+}
+
+static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+
+fn registry() -> &'static Mutex<Registry> {
+    static REGISTRY: OnceLock<Mutex<Registry>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Registry::default()))
+}
+
+fn not_found(id: &str) -> Error {
+    Error::new(Status::InvalidArg, format!("no virtual authenticator with id \"{id}\""))
+}
+
+pub fn has_any() -> bool {
+    !registry().lock().unwrap().authenticators.is_empty()
+}
+
+pub fn add(options: VirtualAuthenticatorOptions) -> Result<String> {
+    let id = format!("virtual-authenticator-{}", NEXT_ID.fetch_add(1, Ordering::SeqCst));
+    let user_verified = options.has_user_verification.unwrap_or(false);
+    let mut reg = registry().lock().unwrap();
+    reg.authenticators.insert(id.clone(), VirtualAuthenticator { options, user_verified, credentials: HashMap::new() });
+    reg.order.push(id.clone());
+    Ok(id)
+}
+
+pub fn remove(id: &str) -> Result<()> {
+    let mut reg = registry().lock().unwrap();
+    reg.authenticators.remove(id).ok_or_else(|| not_found(id))?;
+    reg.order.retain(|existing| existing != id);
+    Ok(())
+}
+
+pub fn set_user_verified(id: &str, verified: bool) -> Result<()> {
+    let mut reg = registry().lock().unwrap();
+    reg.authenticators.get_mut(id).ok_or_else(|| not_found(id))?.user_verified = verified;
+    Ok(())
+}
+
+pub fn add_credential(id: &str, credential: VirtualAuthenticatorCredential) -> Result<()> {
+    let signing_key = SigningKey::from_pkcs8_der(&credential.private_key)
+        .map_err(|e| Error::new(Status::InvalidArg, format!("invalid PKCS#8 private key: {e}")))?;
+
+    let mut reg = registry().lock().unwrap();
+    let authenticator = reg.authenticators.get_mut(id).ok_or_else(|| not_found(id))?;
+    authenticator.credentials.insert(
+        (credential.rp_id, credential.credential_id.to_vec()),
+        StoredCredential {
+            signing_key,
+            sign_count: credential.sign_count,
+            user_handle: credential.user_handle.as_ref().map(|h| h.to_vec()),
+        },
+    );
+    Ok(())
+}
+
+pub fn get_credentials(id: &str) -> Result<Vec<VirtualAuthenticatorCredential>> {
+    let reg = registry().lock().unwrap();
+    let authenticator = reg.authenticators.get(id).ok_or_else(|| not_found(id))?;
+
+    authenticator
+        .credentials
+        .iter()
+        .map(|((rp_id, cred_id), stored)| {
+            let private_key = stored
+                .signing_key
+                .to_pkcs8_der()
+                .map_err(|e| Error::new(Status::GenericFailure, format!("failed to encode private key: {e}")))?;
+            Ok(VirtualAuthenticatorCredential {
+                credential_id: Buffer::from(cred_id.clone()),
+                rp_id: rp_id.clone(),
+                private_key: Buffer::from(private_key.as_bytes().to_vec()),
+                sign_count: stored.sign_count,
+                user_handle: stored.user_handle.clone().map(Buffer::from),
+            })
+        })
+        .collect()
+}
+
+pub async fn create_credential_impl(opts: PublicKeyCredentialCreationOptions) -> Result<PublicKeyCredential> {
+    let rp_id = opts.rp.id.clone().unwrap_or_else(|| opts.rp.name.clone());
+
+    let mut reg = registry().lock().unwrap();
+    let active_id = reg.order.last().cloned().ok_or_else(|| Error::new(Status::GenericFailure, "no virtual authenticator registered"))?;
+    let authenticator = reg.authenticators.get_mut(&active_id).expect("id in `order` is always present in `authenticators`");
+
+    if !authenticator.options.is_user_consenting.unwrap_or(true) {
+        return Err(Error::new(Status::GenericFailure, "virtual authenticator user did not consent"));
+    }
+
+    let requires_uv = opts.authenticator_selection.as_ref().and_then(|s| s.user_verification.as_deref()) == Some("required");
+    if requires_uv && !authenticator.user_verified {
+        return Err(Error::new(Status::GenericFailure, "virtual authenticator user is not verified"));
+    }
+
+    let signing_key = SigningKey::random(&mut OsRng);
+    let verifying_key = VerifyingKey::from(&signing_key);
+
+    let mut cred_id = vec![0u8; 32];
+    OsRng.fill_bytes(&mut cred_id);
+
+    let cose_key = encode_cose_p256_key(&verifying_key);
+    let mut attested_credential_data = Vec::with_capacity(16 + 2 + cred_id.len() + cose_key.len());
+    attested_credential_data.extend_from_slice(&AAGUID);
+    attested_credential_data.extend_from_slice(&(cred_id.len() as u16).to_be_bytes());
+    attested_credential_data.extend_from_slice(&cred_id);
+    attested_credential_data.extend_from_slice(&cose_key);
+
+    let auth_data = build_authenticator_data(&rp_id, authenticator.user_verified, 0, Some(&attested_credential_data));
+    let attestation_object = encode_attestation_object(&auth_data);
+    let client_data_json = build_client_data_json("webauthn.create", &opts.challenge);
+
+    authenticator.credentials.insert(
+        (rp_id, cred_id.clone()),
+        StoredCredential { signing_key, sign_count: 0, user_handle: Some(opts.user.id.to_vec()) },
+    );
+
+    let response = AuthenticatorAttestationResponse {
+        client_data_json: Buffer::from(client_data_json),
+        attestation_object: Buffer::from(attestation_object),
+        transports: vec![authenticator.options.transport.clone()],
+    };
+
+    Ok(PublicKeyCredential {
+        id: base64_url_encode(&cred_id),
+        raw_id: Buffer::from(cred_id),
+        response: Either::A(response),
+        authenticator_attachment: Some("cross-platform".to_string()),
+        type_: "public-key".to_string(),
+        client_extension_results: AuthenticationExtensionsClientOutputs::default(),
+    })
+}
+
+pub async fn get_credential_impl(opts: PublicKeyCredentialRequestOptions) -> Result<PublicKeyCredential> {
+    let rp_id = opts.rp_id.clone().ok_or_else(|| Error::new(Status::InvalidArg, "RP ID is required"))?;
+    let allow_ids: Option<Vec<Vec<u8>>> =
+        opts.allow_credentials.as_ref().map(|list| list.iter().map(|d| d.id.to_vec()).collect());
+
+    let mut reg = registry().lock().unwrap();
+    let (auth_id, cred_id) = find_matching_credential(&reg, &rp_id, allow_ids.as_deref())
+        .ok_or_else(|| Error::new(Status::GenericFailure, "no matching virtual authenticator credential registered for this relying party"))?;
+
+    let authenticator = reg.authenticators.get_mut(&auth_id).expect("looked up above");
+
+    if !authenticator.options.is_user_consenting.unwrap_or(true) {
+        return Err(Error::new(Status::GenericFailure, "virtual authenticator user did not consent"));
+    }
+
+    let requires_uv = opts.user_verification.as_deref() == Some("required");
+    if requires_uv && !authenticator.user_verified {
+        return Err(Error::new(Status::GenericFailure, "virtual authenticator user is not verified"));
+    }
+    let user_verified = authenticator.user_verified;
+
+    let credential = authenticator.credentials.get_mut(&(rp_id.clone(), cred_id.clone())).expect("looked up above");
+    credential.sign_count += 1;
+    let auth_data = build_authenticator_data(&rp_id, user_verified, credential.sign_count, None);
+
+    let client_data_json = build_client_data_json("webauthn.get", &opts.challenge);
+    let client_data_hash = Sha256::digest(&client_data_json);
+
+    let mut signed_payload = auth_data.clone();
+    signed_payload.extend_from_slice(&client_data_hash);
+    let signature: Signature = credential.signing_key.sign(&signed_payload);
+    let user_handle = credential.user_handle.clone();
+
+    let response = AuthenticatorAssertionResponse {
+        client_data_json: Buffer::from(client_data_json),
+        authenticator_data: Buffer::from(auth_data),
+        signature: Buffer::from(signature.to_der().as_bytes().to_vec()),
+        user_handle: user_handle.map(Buffer::from),
+    };
+
+    Ok(PublicKeyCredential {
+        id: base64_url_encode(&cred_id),
+        raw_id: Buffer::from(cred_id),
+        response: Either::B(response),
+        authenticator_attachment: Some("cross-platform".to_string()),
+        type_: "public-key".to_string(),
+        client_extension_results: AuthenticationExtensionsClientOutputs::default(),
+    })
+}
+
+fn find_matching_credential(
+    reg: &MutexGuard<Registry>,
+    rp_id: &str,
+    allow_ids: Option<&[Vec<u8>]>,
+) -> Option<(String, Vec<u8>)> {
+    // Most-recently-added authenticator wins ties, matching `create_credential_impl`'s
+    // notion of "the active authenticator".
+    for auth_id in reg.order.iter().rev() {
+        let authenticator = &reg.authenticators[auth_id];
+        let cred_id = match allow_ids {
+            Some(ids) => ids.iter().find(|id| authenticator.credentials.contains_key(&(rp_id.to_string(), (*id).clone()))).cloned(),
+            None => authenticator.credentials.keys().find(|(rp, _)| rp == rp_id).map(|(_, id)| id.clone()),
+        };
+        if let Some(cred_id) = cred_id {
+            return Some((auth_id.clone(), cred_id));
+        }
+    }
+    None
+}
@@ -1,45 +1,350 @@
-use napi::Result;
-use napi::bindgen_prelude::*;
+//! Windows WebAuthn glue using the native `webauthn.dll` API.
+//! ---------------------------------------------------------------------------
+//! Calls `WebAuthNAuthenticatorMakeCredential`/`WebAuthNAuthenticatorGetAssertion`
+//! (via the `windows` crate's `Win32::Networking::WindowsWebServices` bindings)
+//! against the foreground window, translating `PublicKeyCredentialCreationOptions`/
+//! `PublicKeyCredentialRequestOptions` into the `WEBAUTHN_*` option structs and
+//! the returned attestation/assertion structs back into `PublicKeyCredential`.
+
+#![cfg(target_os = "windows")]
+#![allow(non_snake_case)]
+
+use napi::{bindgen_prelude::*, Error, Result, Status};
+
+use windows::core::PCWSTR;
+use windows::Win32::Foundation::HWND;
+use windows::Win32::Networking::WindowsWebServices::*;
+use windows::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
 use crate::{
-    PublicKeyCredentialCreationOptions, PublicKeyCredentialRequestOptions, 
-    PublicKeyCredential, AuthenticatorAttestationResponse, AuthenticatorAssertionResponse
+    AuthenticationExtensionsClientOutputs, AuthenticatorAssertionResponse, AuthenticatorAttestationResponse,
+    PublicKeyCredential, PublicKeyCredentialCreationOptions, PublicKeyCredentialRequestOptions,
 };
 
-pub fn create_credential_impl(_options: PublicKeyCredentialCreationOptions) -> Result<PublicKeyCredential> {
-    // TODO: Implement Windows WebAuthn API integration
-    // This is a stub implementation
-    
-    let attestation_response = AuthenticatorAttestationResponse {
-        client_data_json: Buffer::from(vec![0u8; 32]), // Placeholder
-        attestation_object: Buffer::from(vec![0u8; 64]), // Placeholder
-        transports: vec!["usb".to_string()],
+pub async fn create_credential_impl(opts: PublicKeyCredentialCreationOptions) -> Result<PublicKeyCredential> {
+    tokio::task::spawn_blocking(move || create_credential_blocking(opts))
+        .await
+        .map_err(|e| Error::new(Status::GenericFailure, format!("native WebAuthn call panicked: {e}")))?
+}
+
+pub async fn get_credential_impl(opts: PublicKeyCredentialRequestOptions) -> Result<PublicKeyCredential> {
+    tokio::task::spawn_blocking(move || get_credential_blocking(opts))
+        .await
+        .map_err(|e| Error::new(Status::GenericFailure, format!("native WebAuthn call panicked: {e}")))?
+}
+
+pub async fn is_supported_impl() -> Result<bool> {
+    let version = unsafe { WebAuthNGetApiVersionNumber() };
+    Ok(version > 0)
+}
+
+// -------------------------------------------------------------------------------------------------
+//  create
+// -------------------------------------------------------------------------------------------------
+
+fn create_credential_blocking(opts: PublicKeyCredentialCreationOptions) -> Result<PublicKeyCredential> {
+    let hwnd = foreground_hwnd();
+
+    let rp_id = wide(opts.rp.id.as_deref().unwrap_or(&opts.rp.name));
+    let rp_name = wide(&opts.rp.name);
+    let rp_info = WEBAUTHN_RP_ENTITY_INFORMATION {
+        dwVersion: WEBAUTHN_API_CURRENT_VERSION,
+        pwszId: PCWSTR(rp_id.as_ptr()),
+        pwszName: PCWSTR(rp_name.as_ptr()),
+        pwszIcon: PCWSTR::null(),
+    };
+
+    let user_name = wide(&opts.user.name);
+    let user_display_name = wide(&opts.user.display_name);
+    let user_info = WEBAUTHN_USER_ENTITY_INFORMATION {
+        dwVersion: WEBAUTHN_API_CURRENT_VERSION,
+        cbId: opts.user.id.len() as u32,
+        pbId: opts.user.id.as_ptr() as *mut u8,
+        pwszName: PCWSTR(user_name.as_ptr()),
+        pwszIcon: PCWSTR::null(),
+        pwszDisplayName: PCWSTR(user_display_name.as_ptr()),
+    };
+
+    let cred_type = wide("public-key");
+    let cose_params: Vec<WEBAUTHN_COSE_CREDENTIAL_PARAMETER> = opts
+        .pub_key_cred_params
+        .iter()
+        .map(|param| WEBAUTHN_COSE_CREDENTIAL_PARAMETER {
+            dwVersion: WEBAUTHN_API_CURRENT_VERSION,
+            pwszCredentialType: PCWSTR(cred_type.as_ptr()),
+            lAlg: param.alg,
+        })
+        .collect();
+    let cose_params_list = WEBAUTHN_COSE_CREDENTIAL_PARAMETERS {
+        cCredentialParameters: cose_params.len() as u32,
+        pCredentialParameters: cose_params.as_ptr() as *mut _,
+    };
+
+    let client_data_json = build_client_data_json("webauthn.create", &opts.challenge);
+    let hash_alg = wide("SHA-256");
+    let client_data = WEBAUTHN_CLIENT_DATA {
+        dwVersion: WEBAUTHN_API_CURRENT_VERSION,
+        cbClientDataJSON: client_data_json.len() as u32,
+        pbClientDataJSON: client_data_json.as_ptr() as *mut u8,
+        pwszHashAlgId: PCWSTR(hash_alg.as_ptr()),
+    };
+
+    let exclude_type = wide("public-key");
+    let exclude_credentials: Vec<Vec<u8>> = opts
+        .exclude_credentials
+        .as_ref()
+        .map(|list| list.iter().map(|d| d.id.to_vec()).collect())
+        .unwrap_or_default();
+    let exclude_entries: Vec<WEBAUTHN_CREDENTIAL_EX> = exclude_credentials
+        .iter()
+        .map(|id| WEBAUTHN_CREDENTIAL_EX {
+            dwVersion: WEBAUTHN_API_CURRENT_VERSION,
+            cbId: id.len() as u32,
+            pbId: id.as_ptr() as *mut u8,
+            pwszCredentialType: PCWSTR(exclude_type.as_ptr()),
+            dwTransports: WEBAUTHN_CTAP_TRANSPORT_FLAGS_ALL,
+        })
+        .collect();
+    let mut exclude_entry_ptrs: Vec<*mut WEBAUTHN_CREDENTIAL_EX> =
+        exclude_entries.iter().map(|e| e as *const _ as *mut _).collect();
+    let mut exclude_list = WEBAUTHN_CREDENTIAL_LIST {
+        cCredentials: exclude_entry_ptrs.len() as u32,
+        ppCredentials: exclude_entry_ptrs.as_mut_ptr(),
+    };
+
+    let user_verification = user_verification_requirement(
+        opts.authenticator_selection.as_ref().and_then(|s| s.user_verification.as_deref()),
+    );
+    let attachment = authenticator_attachment(
+        opts.authenticator_selection.as_ref().and_then(|s| s.authenticator_attachment.as_deref()),
+    );
+    let resident_key_required = opts
+        .authenticator_selection
+        .as_ref()
+        .and_then(|s| s.require_resident_key)
+        .unwrap_or(false);
+    let attestation = attestation_conveyance(opts.attestation.as_deref());
+
+    let make_cred_options = WEBAUTHN_AUTHENTICATOR_MAKE_CREDENTIAL_OPTIONS {
+        dwVersion: WEBAUTHN_API_CURRENT_VERSION,
+        dwTimeoutMilliseconds: opts.timeout.unwrap_or(60_000) as u32,
+        CredentialList: WEBAUTHN_CREDENTIALS { cCredentials: 0, pCredentials: std::ptr::null_mut() },
+        Extensions: WEBAUTHN_EXTENSIONS { cExtensions: 0, pExtensions: std::ptr::null_mut() },
+        dwAuthenticatorAttachment: attachment,
+        bRequireResidentKey: resident_key_required.into(),
+        dwUserVerificationRequirement: user_verification,
+        dwAttestationConveyancePreference: attestation,
+        dwFlags: 0,
+        pCancellationId: std::ptr::null_mut(),
+        pExcludeCredentialList: &mut exclude_list,
+        dwEnterpriseAttestation: 0,
+        dwLargeBlobSupport: 0,
+        bPreferResidentKey: resident_key_required.into(),
+        bBrowserInPrivateMode: false.into(),
+        bEnablePrf: false.into(),
+        pLinkedDevice: std::ptr::null_mut(),
+        cbJsonExt: 0,
+        pbJsonExt: std::ptr::null_mut(),
+    };
+
+    let mut attestation_ptr: *mut WEBAUTHN_CREDENTIAL_ATTESTATION = std::ptr::null_mut();
+    unsafe {
+        WebAuthNAuthenticatorMakeCredential(hwnd, &rp_info, &user_info, &cose_params_list, &client_data, Some(&make_cred_options), &mut attestation_ptr)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("WebAuthNAuthenticatorMakeCredential failed: {e}")))?;
+
+        let result = parse_attestation(&*attestation_ptr);
+        WebAuthNFreeCredentialAttestation(Some(attestation_ptr));
+        result
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+//  get
+// -------------------------------------------------------------------------------------------------
+
+fn get_credential_blocking(opts: PublicKeyCredentialRequestOptions) -> Result<PublicKeyCredential> {
+    let hwnd = foreground_hwnd();
+
+    let rp_id = wide(opts.rp_id.as_deref().ok_or_else(|| Error::new(Status::InvalidArg, "RP ID is required"))?);
+
+    let client_data_json = build_client_data_json("webauthn.get", &opts.challenge);
+    let hash_alg = wide("SHA-256");
+    let client_data = WEBAUTHN_CLIENT_DATA {
+        dwVersion: WEBAUTHN_API_CURRENT_VERSION,
+        cbClientDataJSON: client_data_json.len() as u32,
+        pbClientDataJSON: client_data_json.as_ptr() as *mut u8,
+        pwszHashAlgId: PCWSTR(hash_alg.as_ptr()),
+    };
+
+    let allow_type = wide("public-key");
+    let allow_credentials: Vec<Vec<u8>> = opts
+        .allow_credentials
+        .as_ref()
+        .map(|list| list.iter().map(|d| d.id.to_vec()).collect())
+        .unwrap_or_default();
+    let allow_entries: Vec<WEBAUTHN_CREDENTIAL_EX> = allow_credentials
+        .iter()
+        .map(|id| WEBAUTHN_CREDENTIAL_EX {
+            dwVersion: WEBAUTHN_API_CURRENT_VERSION,
+            cbId: id.len() as u32,
+            pbId: id.as_ptr() as *mut u8,
+            pwszCredentialType: PCWSTR(allow_type.as_ptr()),
+            dwTransports: WEBAUTHN_CTAP_TRANSPORT_FLAGS_ALL,
+        })
+        .collect();
+    let mut allow_entry_ptrs: Vec<*mut WEBAUTHN_CREDENTIAL_EX> =
+        allow_entries.iter().map(|e| e as *const _ as *mut _).collect();
+    let mut allow_list = WEBAUTHN_CREDENTIAL_LIST {
+        cCredentials: allow_entry_ptrs.len() as u32,
+        ppCredentials: allow_entry_ptrs.as_mut_ptr(),
     };
-    
+
+    let user_verification = user_verification_requirement(opts.user_verification.as_deref());
+
+    let get_assertion_options = WEBAUTHN_AUTHENTICATOR_GET_ASSERTION_OPTIONS {
+        dwVersion: WEBAUTHN_API_CURRENT_VERSION,
+        dwTimeoutMilliseconds: opts.timeout.unwrap_or(60_000) as u32,
+        CredentialList: WEBAUTHN_CREDENTIALS { cCredentials: 0, pCredentials: std::ptr::null_mut() },
+        Extensions: WEBAUTHN_EXTENSIONS { cExtensions: 0, pExtensions: std::ptr::null_mut() },
+        dwAuthenticatorAttachment: WEBAUTHN_AUTHENTICATOR_ATTACHMENT_ANY,
+        dwUserVerificationRequirement: user_verification,
+        dwFlags: 0,
+        pwszU2fAppId: PCWSTR::null(),
+        pbU2fAppId: std::ptr::null_mut(),
+        pCancellationId: std::ptr::null_mut(),
+        pAllowCredentialList: &mut allow_list,
+        dwCredLargeBlobOperation: 0,
+        cbCredLargeBlob: 0,
+        pbCredLargeBlob: std::ptr::null_mut(),
+        pHmacSecretSaltValues: std::ptr::null_mut(),
+        bBrowserInPrivateMode: false.into(),
+        pLinkedDevice: std::ptr::null_mut(),
+        bAutoFill: false.into(),
+        cbJsonExt: 0,
+        pbJsonExt: std::ptr::null_mut(),
+    };
+
+    let mut assertion_ptr: *mut WEBAUTHN_ASSERTION = std::ptr::null_mut();
+    unsafe {
+        WebAuthNAuthenticatorGetAssertion(hwnd, &rp_id, &client_data, Some(&get_assertion_options), &mut assertion_ptr)
+            .map_err(|e| Error::new(Status::GenericFailure, format!("WebAuthNAuthenticatorGetAssertion failed: {e}")))?;
+
+        let result = parse_assertion(&*assertion_ptr, client_data_json);
+        WebAuthNFreeAssertion(assertion_ptr);
+        result
+    }
+}
+
+// -------------------------------------------------------------------------------------------------
+//  Result parsing
+// -------------------------------------------------------------------------------------------------
+
+unsafe fn parse_attestation(attestation: &WEBAUTHN_CREDENTIAL_ATTESTATION) -> Result<PublicKeyCredential> {
+    let client_data_json = slice_from_raw(attestation.pbClientDataJSON, attestation.cbClientDataJSON).to_vec();
+    let attestation_object = slice_from_raw(attestation.pbAttestationObject, attestation.cbAttestationObject).to_vec();
+    let cred_id = slice_from_raw(attestation.pbCredentialId, attestation.cbCredentialId).to_vec();
+
+    let response = AuthenticatorAttestationResponse {
+        client_data_json: Buffer::from(client_data_json),
+        attestation_object: Buffer::from(attestation_object),
+        transports: vec!["usb".to_string(), "nfc".to_string(), "ble".to_string(), "internal".to_string()],
+    };
+
     Ok(PublicKeyCredential {
-        id: "stub-credential-id".to_string(),
-        raw_id: Buffer::from(vec![0u8; 16]),
-        response: Either::A(attestation_response),
-        authenticator_attachment: Some("platform".to_string()),
+        id: base64_url_encode(&cred_id),
+        raw_id: Buffer::from(cred_id),
+        response: Either::A(response),
+        authenticator_attachment: Some("cross-platform".to_string()),
         type_: "public-key".to_string(),
+        client_extension_results: AuthenticationExtensionsClientOutputs::default(),
     })
 }
 
-pub fn get_credential_impl(_options: PublicKeyCredentialRequestOptions) -> Result<PublicKeyCredential> {
-    // TODO: Implement Windows WebAuthn API integration
-    // This is a stub implementation
-    
-    let assertion_response = AuthenticatorAssertionResponse {
-        client_data_json: Buffer::from(vec![0u8; 32]), // Placeholder
-        authenticator_data: Buffer::from(vec![0u8; 37]), // Placeholder
-        signature: Buffer::from(vec![0u8; 64]), // Placeholder
-        user_handle: None,
+unsafe fn parse_assertion(assertion: &WEBAUTHN_ASSERTION, client_data_json: Vec<u8>) -> Result<PublicKeyCredential> {
+    let authenticator_data = slice_from_raw(assertion.pbAuthenticatorData, assertion.cbAuthenticatorData).to_vec();
+    let signature = slice_from_raw(assertion.pbSignature, assertion.cbSignature).to_vec();
+    let cred_id = slice_from_raw(assertion.Credential.pbId, assertion.Credential.cbId).to_vec();
+    let user_handle = if assertion.cbUserId > 0 {
+        Some(Buffer::from(slice_from_raw(assertion.pbUserId, assertion.cbUserId).to_vec()))
+    } else {
+        None
+    };
+
+    // The client data JSON is not echoed back by `WEBAUTHN_ASSERTION`; we rebuild it from
+    // the request we issued, matching what the authenticator actually signed over.
+    let response = AuthenticatorAssertionResponse {
+        client_data_json: Buffer::from(client_data_json),
+        authenticator_data: Buffer::from(authenticator_data),
+        signature: Buffer::from(signature),
+        user_handle,
     };
-    
+
     Ok(PublicKeyCredential {
-        id: "stub-credential-id".to_string(),
-        raw_id: Buffer::from(vec![0u8; 16]),
-        response: Either::B(assertion_response),
-        authenticator_attachment: Some("platform".to_string()),
+        id: base64_url_encode(&cred_id),
+        raw_id: Buffer::from(cred_id),
+        response: Either::B(response),
+        authenticator_attachment: Some("cross-platform".to_string()),
         type_: "public-key".to_string(),
+        client_extension_results: AuthenticationExtensionsClientOutputs::default(),
     })
-} 
\ No newline at end of file
+}
+
+// -------------------------------------------------------------------------------------------------
+//  Utility helpers
+// -------------------------------------------------------------------------------------------------
+
+fn foreground_hwnd() -> HWND {
+    unsafe { GetForegroundWindow() }
+}
+
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+fn build_client_data_json(ty: &str, challenge: &[u8]) -> Vec<u8> {
+    format!(
+        "{{\"type\":\"{}\",\"challenge\":\"{}\"}}",
+        ty,
+        base64_url_encode(challenge)
+    )
+    .into_bytes()
+}
+
+fn base64_url_encode(data: &[u8]) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(data)
+}
+
+unsafe fn slice_from_raw<'a>(ptr: *const u8, len: u32) -> &'a [u8] {
+    if ptr.is_null() || len == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(ptr, len as usize)
+    }
+}
+
+fn user_verification_requirement(preference: Option<&str>) -> WEBAUTHN_USER_VERIFICATION_REQUIREMENT {
+    match preference {
+        Some("required") => WEBAUTHN_USER_VERIFICATION_REQUIREMENT_REQUIRED,
+        Some("discouraged") => WEBAUTHN_USER_VERIFICATION_REQUIREMENT_DISCOURAGED,
+        _ => WEBAUTHN_USER_VERIFICATION_REQUIREMENT_PREFERRED,
+    }
+}
+
+fn authenticator_attachment(preference: Option<&str>) -> WEBAUTHN_AUTHENTICATOR_ATTACHMENT {
+    match preference {
+        Some("platform") => WEBAUTHN_AUTHENTICATOR_ATTACHMENT_PLATFORM,
+        Some("cross-platform") => WEBAUTHN_AUTHENTICATOR_ATTACHMENT_CROSS_PLATFORM,
+        _ => WEBAUTHN_AUTHENTICATOR_ATTACHMENT_ANY,
+    }
+}
+
+fn attestation_conveyance(preference: Option<&str>) -> WEBAUTHN_ATTESTATION_CONVEYANCE_PREFERENCE {
+    match preference {
+        Some("none") => WEBAUTHN_ATTESTATION_CONVEYANCE_PREFERENCE_NONE,
+        Some("indirect") => WEBAUTHN_ATTESTATION_CONVEYANCE_PREFERENCE_INDIRECT,
+        Some("direct") => WEBAUTHN_ATTESTATION_CONVEYANCE_PREFERENCE_DIRECT,
+        Some("enterprise") => WEBAUTHN_ATTESTATION_CONVEYANCE_PREFERENCE_ENTERPRISE,
+        _ => WEBAUTHN_ATTESTATION_CONVEYANCE_PREFERENCE_ANY,
+    }
+}